@@ -1,69 +1,270 @@
-use std::{collections::BTreeSet, fmt::Display};
+use std::{collections::BTreeSet, fmt::Display, time::SystemTime};
 
 fn main() -> Result<()> {
     let mut args = std::env::args();
     let _ = args.next();
-    let input = args.next().ok_or_else(|| "no input file specified")?;
-    let input = std::fs::read_to_string(input)?;
-    run_input(&input)?;
+    let mut render = false;
+    let mut trails = false;
+    let mut generate = None;
+    let mut input_file = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--render" => render = true,
+            "--trails" => trails = true,
+            "--generate" => {
+                let n = args.next().ok_or("--generate needs a count")?;
+                generate = Some(n.parse::<usize>()?);
+            }
+            _ => input_file = Some(arg),
+        }
+    }
+    let input = if let Some(n) = generate {
+        let input = generate_input(n);
+        print!("{input}");
+        input
+    } else {
+        let input = input_file.ok_or("no input file specified")?;
+        std::fs::read_to_string(input)?
+    };
+    run_input(&input, render, trails)?;
     Ok(())
 }
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn parse_input(s: &str) -> Result<Input> {
-    let mut lines = s.lines();
-    let Some(world) = lines.next() else {
-        return Err("no grid found")?;
-    };
-    let world_size = world
-        .split_whitespace()
-        .filter_map(|val| val.parse::<i8>().ok())
-        .collect::<Vec<_>>();
-    if world_size.len() != 2 {
-        return Err(format!("could not parse world size: {world}"))?;
+/// A tiny xorshift generator so we can synthesise inputs without pulling in an
+/// external rng dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn from_clock() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9e3779b97f4a7c15);
+        Rng(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform in `[low, high]` for small inclusive ranges.
+    fn range(&mut self, low: i8, high: i8) -> i8 {
+        let span = (high - low) as u64 + 1;
+        low + (self.next_u64() % span) as i8
     }
+}
+
+/// Synthesise a random but spec-valid textual `Input` with `n` robots.
+fn generate_input(n: usize) -> String {
+    let mut rng = Rng::from_clock();
+    let max_x = rng.range(1, 50);
+    let max_y = rng.range(1, 50);
+    let mut out = format!("{max_x} {max_y}\n");
+    let orientations = ['N', 'E', 'S', 'W'];
+    let alphabet = ['F', 'L', 'R'];
+    for _ in 0..n {
+        let start_x = rng.range(0, max_x);
+        let start_y = rng.range(0, max_y);
+        let orientation = orientations[rng.range(0, 3) as usize];
+        let len = rng.range(5, 10);
+        let commands: String = (0..len).map(|_| alphabet[rng.range(0, 2) as usize]).collect();
+        out.push_str(&format!("\n{start_x} {start_y} {orientation}\n{commands}\n"));
+    }
+    out
+}
+
+/// A structured parse failure pointing at the exact 1-based line and column
+/// where the grammar broke, together with what was expected there.
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    line: usize,
+    col: usize,
+    expected: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parse error at line {}, column {}: expected {}",
+            self.line, self.col, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A single-line cursor that tracks its column so the parser can report where
+/// it stopped. Lines are handled individually, so the cursor only needs to
+/// know which line number it sits on.
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Cursor {
+    fn new(s: &str, line: usize) -> Self {
+        Cursor {
+            chars: s.chars().collect(),
+            pos: 0,
+            line,
+        }
+    }
+
+    fn col(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn err(&self, expected: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            col: self.col(),
+            expected: expected.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// `DIGIT+` parsed into the `i8` the rest of the code uses for coordinates.
+    fn uint(&mut self) -> std::result::Result<i8, ParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.err("a digit"));
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse::<i8>().map_err(|_| ParseError {
+            line: self.line,
+            col: start + 1,
+            expected: "a number in 0..=127".to_string(),
+        })
+    }
+
+    fn expect(&mut self, c: char) -> std::result::Result<(), ParseError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.err(&format!("'{c}'")))
+        }
+    }
+
+    /// One of `N`/`E`/`S`/`W`.
+    fn orientation(&mut self) -> std::result::Result<Orientation, ParseError> {
+        let o = match self.peek() {
+            Some('N') => Orientation::North,
+            Some('E') => Orientation::East,
+            Some('S') => Orientation::South,
+            Some('W') => Orientation::West,
+            _ => return Err(self.err("one of N, E, S, W")),
+        };
+        self.pos += 1;
+        Ok(o)
+    }
+
+    /// `("F"|"L"|"R")*` — an empty instruction line is valid.
+    fn instructions(&mut self) -> std::result::Result<Vec<Command>, ParseError> {
+        let mut commands = Vec::new();
+        while let Some(c) = self.peek() {
+            let cmd = match c {
+                'F' => Command::Forward,
+                'L' => Command::Left,
+                'R' => Command::Right,
+                _ => return Err(self.err("one of F, L, R")),
+            };
+            commands.push(cmd);
+            self.pos += 1;
+        }
+        Ok(commands)
+    }
+
+    fn eol(&mut self) -> std::result::Result<(), ParseError> {
+        if self.pos < self.chars.len() {
+            Err(self.err("end of line"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Parse a whole input against the grammar:
+///
+/// ```text
+/// World        = Header NEWLINE+ Robot+
+/// Header       = DIGIT+ " " DIGIT+
+/// Robot        = DIGIT+ " " DIGIT+ " " ("N"|"E"|"S"|"W") NEWLINE Instructions NEWLINE+
+/// Instructions = ("F"|"L"|"R")*
+/// ```
+///
+/// The parser is looser than this grammar at the end of the input: a header
+/// with no robots parses to an empty robot list, the final newline may be
+/// omitted, and a last robot with no instruction line gets no commands.
+fn parse_input(s: &str) -> std::result::Result<Input, ParseError> {
+    let lines: Vec<&str> = s.lines().collect();
+    let header = lines.first().ok_or(ParseError {
+        line: 1,
+        col: 1,
+        expected: "grid dimensions".to_string(),
+    })?;
+    let mut c = Cursor::new(header, 1);
+    let max_x = c.uint()?;
+    c.expect(' ')?;
+    let max_y = c.uint()?;
+    c.eol()?;
+
     let mut robot_inputs = Vec::new();
+    let mut i = 1;
     loop {
-        let Some(ri) = parse_robot_input(&mut lines)? else { break };
-        robot_inputs.push(ri);
+        // NEWLINE+ between robots: skip blank separator lines.
+        while i < lines.len() && lines[i].is_empty() {
+            i += 1;
+        }
+        if i >= lines.len() {
+            break;
+        }
+        let mut c = Cursor::new(lines[i], i + 1);
+        let start_x = c.uint()?;
+        c.expect(' ')?;
+        let start_y = c.uint()?;
+        c.expect(' ')?;
+        let start_orientation = c.orientation()?;
+        c.eol()?;
+        i += 1;
+        // The very next line is the instruction line, even when it is empty;
+        // a trailing robot with no instruction line defaults to no commands.
+        let commands = if i < lines.len() {
+            let cmds = Cursor::new(lines[i], i + 1).instructions()?;
+            i += 1;
+            cmds
+        } else {
+            Vec::new()
+        };
+        robot_inputs.push(RobotInput {
+            start_x,
+            start_y,
+            start_orientation,
+            commands,
+        });
     }
     Ok(Input {
-        init_world: (world_size[0], world_size[1]),
+        init_world: (max_x, max_y),
         robot_inputs,
     })
 }
 
-fn parse_robot_input<'a>(mut s: impl Iterator<Item = &'a str>) -> Result<Option<RobotInput>> {
-    let startpos = loop {
-        match s.next() {
-            None => return Ok(None),
-            Some("") => continue,
-            Some(s) => break s,
-        }
-    };
-    let start: Vec<_> = startpos.split_whitespace().collect();
-    if start.len() != 3 {
-        return Err(format!("Could not parse robot start pos: {startpos}"))?;
-    }
-    let start_x = start[0].parse::<i8>()?;
-    let start_y = start[1].parse::<i8>()?;
-    let start_orientation = Orientation::parse(&start[2])?;
-    let Some(cmdstr) = s.next() else {
-        return Err("no commands found")?;
-    };
-    let commands = cmdstr
-        .chars()
-        .map(|val| Command::parse(val))
-        .collect::<Result<Vec<_>>>()?;
-    Ok(Some(RobotInput {
-        start_x,
-        start_y,
-        start_orientation,
-        commands,
-    }))
-}
-
+#[derive(Debug)]
 struct RobotInput {
     start_x: i8,
     start_y: i8,
@@ -71,6 +272,7 @@ struct RobotInput {
     commands: Vec<Command>,
 }
 
+#[derive(Debug)]
 struct Input {
     init_world: (i8, i8),
     robot_inputs: Vec<RobotInput>,
@@ -98,18 +300,6 @@ enum Command {
     Right,
 }
 
-impl Command {
-    fn parse(i: char) -> Result<Self> {
-        let cmd = match i {
-            'F' => Command::Forward,
-            'L' => Command::Left,
-            'R' => Command::Right,
-            _ => return Err(format!("not a valid command: '{i}'"))?,
-        };
-        Ok(cmd)
-    }
-}
-
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum Orientation {
     North,
@@ -161,15 +351,13 @@ impl Orientation {
 }
 
 impl Orientation {
-    fn parse(i: &str) -> Result<Self> {
-        let cmd = match i {
-            "N" => Orientation::North,
-            "E" => Orientation::East,
-            "S" => Orientation::South,
-            "W" => Orientation::West,
-            _ => return Err(format!("not a valid orientation: '{i}'"))?,
-        };
-        Ok(cmd)
+    fn glyph(&self) -> char {
+        match self {
+            Orientation::North => '↑',
+            Orientation::East => '→',
+            Orientation::South => '↓',
+            Orientation::West => '←',
+        }
     }
 }
 
@@ -177,6 +365,7 @@ struct World {
     max_x: i8,
     max_y: i8,
     tombstones: BTreeSet<(i8, i8)>,
+    occupied: BTreeSet<(i8, i8)>,
 }
 
 impl World {
@@ -188,10 +377,13 @@ impl World {
             max_x,
             max_y,
             tombstones: Default::default(),
+            occupied: Default::default(),
         })
     }
 
     fn run(&mut self, mut robot: Robot, commands: &[Command]) -> Outcome {
+        let mut blocked = false;
+        let mut path = vec![(robot.x, robot.y, robot.orientation)];
         for c in commands {
             match c {
                 Command::Forward => {
@@ -201,18 +393,29 @@ impl World {
                     if self.tombstones.contains(&(new_x, new_y)) {
                         continue;
                     }
+                    // another resting robot holds the target cell: refuse the
+                    // move just like a scented edge, but remember we stopped
+                    // against an obstacle so the caller can report it.
+                    if self.occupied.contains(&(new_x, new_y)) {
+                        blocked = true;
+                        continue;
+                    }
                     if new_x > self.max_x || new_y > self.max_y || new_x < 0 || new_y < 0 {
-                        // illegal move
+                        // illegal move: the robot drives off the edge and is
+                        // lost, so it vacates its last cell (no occupancy).
                         self.tombstones.insert((new_x, new_y));
                         return Outcome {
                             final_x: robot.x,
                             final_y: robot.y,
                             final_orientation: robot.orientation,
                             lost: true,
+                            blocked: false,
+                            path,
                         };
                     }
                     robot.x = new_x;
                     robot.y = new_y;
+                    blocked = false;
                 }
                 Command::Left => {
                     robot.orientation = robot.orientation.left();
@@ -221,13 +424,97 @@ impl World {
                     robot.orientation = robot.orientation.right();
                 }
             }
+            path.push((robot.x, robot.y, robot.orientation));
         }
+        // a surviving robot comes to rest and now blocks its cell
+        self.occupied.insert((robot.x, robot.y));
         Outcome {
             final_x: robot.x,
             final_y: robot.y,
             final_orientation: robot.orientation,
             lost: false,
+            blocked,
+            path,
+        }
+    }
+
+    /// Clamp a possibly off-grid coordinate (such as a tombstone) back onto
+    /// the board, yielding the last in-grid cell a robot occupied.
+    fn clamp_to_grid(&self, x: i8, y: i8) -> (i8, i8) {
+        (x.clamp(0, self.max_x), y.clamp(0, self.max_y))
+    }
+
+    fn render(&self, robots: &[Robot]) -> String {
+        let width = (self.max_x as usize) + 1;
+        let height = (self.max_y as usize) + 1;
+        let mut buf = vec!['.'; width * height];
+        let mut stamp = |x: i8, y: i8, glyph: char| {
+            if x < 0 || y < 0 || x > self.max_x || y > self.max_y {
+                return;
+            }
+            let idx = (self.max_y - y) as usize * width + x as usize;
+            buf[idx] = glyph;
+        };
+        for robot in robots {
+            stamp(robot.x, robot.y, robot.orientation.glyph());
+        }
+        // A tombstone records the off-grid cell a lost robot drove into; the
+        // scent it leaves belongs to the last in-grid cell it stood on, so
+        // clamp back onto the board and mark that with the scent glyph.
+        for &(x, y) in &self.tombstones {
+            let (x, y) = self.clamp_to_grid(x, y);
+            stamp(x, y, '*');
+        }
+        let mut out = String::with_capacity((width + 1) * height);
+        for row in buf.chunks(width) {
+            out.extend(row);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Overlay every robot's trail onto one grid as a heat map: `.` for an
+    /// untouched cell, `1`..=`9` for the visit count, `#` for a cell crossed
+    /// ten or more times, and the scent glyph `*` wherever a robot was lost.
+    fn render_trails(&self, outcomes: &[Outcome]) -> String {
+        let width = (self.max_x as usize) + 1;
+        let height = (self.max_y as usize) + 1;
+        let mut counts = vec![0u32; width * height];
+        for outcome in outcomes {
+            let mut prev = None;
+            for &(x, y, _) in &outcome.path {
+                if x < 0 || y < 0 || x > self.max_x || y > self.max_y {
+                    continue;
+                }
+                // turning in place shouldn't inflate a cell's traffic
+                if prev == Some((x, y)) {
+                    continue;
+                }
+                prev = Some((x, y));
+                counts[(self.max_y - y) as usize * width + x as usize] += 1;
+            }
+        }
+        let mut buf: Vec<char> = counts
+            .iter()
+            .map(|&n| match n {
+                0 => '.',
+                1..=9 => (b'0' + n as u8) as char,
+                _ => '#',
+            })
+            .collect();
+        // Tombstones hold the off-grid cell a lost robot drove into; clamp
+        // each back to the last in-grid cell so the scent glyph lands on the
+        // board rather than being silently dropped.
+        for &(x, y) in &self.tombstones {
+            let (x, y) = self.clamp_to_grid(x, y);
+            buf[(self.max_y - y) as usize * width + x as usize] = '*';
+        }
+        let mut out = String::with_capacity((width + 1) * height);
+        for row in buf.chunks(width) {
+            out.extend(row);
+            out.push('\n');
         }
+        out
     }
 }
 
@@ -237,6 +524,10 @@ struct Outcome {
     final_y: i8,
     final_orientation: Orientation,
     lost: bool,
+    blocked: bool,
+    /// The full (x, y, orientation) trail the robot walked, starting at its
+    /// spawn point, so a run can be replayed or animated after the fact.
+    path: Vec<(i8, i8, Orientation)>,
 }
 
 struct Robot {
@@ -258,17 +549,31 @@ impl Robot {
     }
 }
 
-fn run_input(input: &str) -> Result<()> {
+fn run_input(input: &str, render: bool, trails: bool) -> Result<()> {
     let input = parse_input(input)?;
     let (mut world, robots) = input.into_world()?;
     let outcomes: Vec<_> = robots.into_iter().map(|(r, c)| world.run(r, &c)).collect();
-    for o in outcomes {
+    for o in &outcomes {
         print!("{} {} {}", o.final_x, o.final_y, o.final_orientation);
         if o.lost {
             print!(" LOST");
         }
         println!()
     }
+    if render {
+        let resting: Vec<_> = outcomes
+            .iter()
+            .map(|o| Robot {
+                x: o.final_x,
+                y: o.final_y,
+                orientation: o.final_orientation,
+            })
+            .collect();
+        print!("{}", world.render(&resting));
+    }
+    if trails {
+        print!("{}", world.render_trails(&outcomes));
+    }
     Ok(())
 }
 
@@ -291,33 +596,110 @@ LLFFFLFLFL"#;
         assert_eq!(input.robot_inputs.len(), 3);
         let (mut world, robots) = input.into_world().unwrap();
         let outcomes: Vec<_> = robots.into_iter().map(|(r, c)| world.run(r, &c)).collect();
+        let summary: Vec<_> = outcomes
+            .iter()
+            .map(|o| (o.final_x, o.final_y, o.final_orientation, o.lost))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, 1, Orientation::East, false),
+                (3, 3, Orientation::North, true),
+                (2, 3, Orientation::South, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let input = r#"5 3
+1 1 E
+RFRFRFRF
+
+3 2 N
+FRRFLLFFRRFLL
+
+0 3 W
+LLFFFLFLFL"#;
+        let input = parse_input(input).unwrap();
+        let (mut world, robots) = input.into_world().unwrap();
+        let resting: Vec<_> = robots
+            .into_iter()
+            .map(|(r, c)| world.run(r, &c))
+            .map(|o| Robot {
+                x: o.final_x,
+                y: o.final_y,
+                orientation: o.final_orientation,
+            })
+            .collect();
+        assert_eq!(
+            world.render(&resting),
+            "..↓*..\n......\n.→....\n......\n"
+        );
+    }
+
+    #[test]
+    fn test_robot_blocks_robot() {
+        let input = "5 5\n1 1 N\nL\n\n0 1 E\nF";
+        let input = parse_input(input).unwrap();
+        let (mut world, robots) = input.into_world().unwrap();
+        let outcomes: Vec<_> = robots.into_iter().map(|(r, c)| world.run(r, &c)).collect();
+        // first robot comes to rest on (1, 1); the second is refused entry
+        let summary: Vec<_> = outcomes
+            .iter()
+            .map(|o| (o.final_x, o.final_y, o.final_orientation, o.lost, o.blocked))
+            .collect();
         assert_eq!(
-            outcomes,
+            summary,
             [
-                Outcome {
-                    final_x: 1,
-                    final_y: 1,
-                    final_orientation: Orientation::East,
-                    lost: false
-                },
-                Outcome {
-                    final_x: 3,
-                    final_y: 3,
-                    final_orientation: Orientation::North,
-                    lost: true
-                },
-                Outcome {
-                    final_x: 2,
-                    final_y: 3,
-                    final_orientation: Orientation::South,
-                    lost: false
-                },
+                (1, 1, Orientation::West, false, false),
+                (0, 1, Orientation::East, false, true),
             ]
         );
     }
 
+    #[test]
+    fn test_render_trails() {
+        let input = parse_input("2 2\n0 0 N\nFF").unwrap();
+        let (mut world, robots) = input.into_world().unwrap();
+        let outcomes: Vec<_> = robots.into_iter().map(|(r, c)| world.run(r, &c)).collect();
+        assert_eq!(outcomes[0].path.len(), 3);
+        assert_eq!(world.render_trails(&outcomes), "1..\n1..\n1..\n");
+    }
+
+    #[test]
+    fn test_generate_is_runnable() {
+        let text = generate_input(10);
+        let input = parse_input(&text).unwrap();
+        assert_eq!(input.robot_inputs.len(), 10);
+        let (mut world, robots) = input.into_world().unwrap();
+        // every generated robot should run to completion without panicking
+        let _: Vec<_> = robots.into_iter().map(|(r, c)| world.run(r, &c)).collect();
+    }
+
     #[test]
     fn test_bad_input_errors() {
         assert!(parse_input("5 5\n1 2 3").is_err())
     }
+
+    #[test]
+    fn test_empty_instruction_line() {
+        let input = parse_input("5 5\n1 1 N\n\n2 2 E\nF").unwrap();
+        assert_eq!(input.robot_inputs.len(), 2);
+        assert!(input.robot_inputs[0].commands.is_empty());
+        assert_eq!(input.robot_inputs[1].commands.len(), 1);
+    }
+
+    #[test]
+    fn test_error_reports_location() {
+        let err = parse_input("5 5\n1 1 X").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                line: 2,
+                col: 5,
+                expected: "one of N, E, S, W".to_string(),
+            }
+        );
+    }
 }